@@ -0,0 +1,312 @@
+//! Config-file-driven batch processing over a directory of foils.
+
+use crate::{
+    get_px_deltas_from_lines, load_image, measurements_from_image, render_overlay,
+    MeasurementMode, ScanDirection,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Batch-run configuration: where to find foils and their masks, how to scan them, and where
+/// to write results.
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    pub input_dir: PathBuf,
+    pub mask_dir: Option<PathBuf>,
+    #[serde(default = "default_scan_directions")]
+    pub scan_directions: Vec<ScanDirection>,
+    pub output_path: PathBuf,
+    #[serde(default)]
+    pub output_format: OutputFormat,
+    #[serde(default = "default_histogram_bins")]
+    pub histogram_bins: usize,
+    /// If set, an annotated QC overlay PNG is written here for every input image, under the
+    /// same file name, via [`render_overlay`].
+    #[serde(default)]
+    pub overlay_dir: Option<PathBuf>,
+}
+
+fn default_scan_directions() -> Vec<ScanDirection> {
+    vec![ScanDirection::Horizontal]
+}
+
+fn default_histogram_bins() -> usize {
+    10
+}
+
+/// Which file format(s) `write_results` emits.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    #[default]
+    Csv,
+    Json,
+    Both,
+}
+
+/// Loads and parses a [`Config`] from a `.toml` file at `path`.
+pub fn load_config(path: &Path) -> Result<Config> {
+    let text = fs::read_to_string(path)?;
+    let config: Config = toml::from_str(&text)?;
+    Ok(config)
+}
+
+/// The pixel deltas measured from a single image, plus the summary statistics computed from
+/// them.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageResult {
+    pub image_path: PathBuf,
+    pub deltas: Vec<usize>,
+    pub stats: Stats,
+}
+
+/// Summary statistics and a binned histogram over a set of pixel deltas.
+#[derive(Debug, Clone, Serialize)]
+pub struct Stats {
+    pub count: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub mode: usize,
+    pub std_dev: f64,
+    /// `(bin_start, count)` pairs, in ascending bin order.
+    pub histogram: Vec<(usize, usize)>,
+}
+
+/// Computes [`Stats`] over `deltas`, bucketing the histogram into `bins` equal-width bins
+/// starting at `deltas`' min and covering min..=max (see [`build_histogram`]).
+fn compute_stats(deltas: &[usize], bins: usize) -> Stats {
+    if deltas.is_empty() {
+        return Stats {
+            count: 0,
+            mean: 0.0,
+            median: 0.0,
+            mode: 0,
+            std_dev: 0.0,
+            histogram: Vec::new(),
+        };
+    }
+
+    let count = deltas.len();
+    let mean = deltas.iter().sum::<usize>() as f64 / count as f64;
+
+    let mut sorted = deltas.to_vec();
+    sorted.sort_unstable();
+    let median = if count.is_multiple_of(2) {
+        (sorted[count / 2 - 1] + sorted[count / 2]) as f64 / 2.0
+    } else {
+        sorted[count / 2] as f64
+    };
+
+    let mut frequencies: HashMap<usize, usize> = HashMap::new();
+    for &delta in deltas {
+        *frequencies.entry(delta).or_insert(0) += 1;
+    }
+    let mode = frequencies
+        .into_iter()
+        .max_by_key(|&(_, freq)| freq)
+        .map(|(value, _)| value)
+        .unwrap_or(0);
+
+    let variance = deltas
+        .iter()
+        .map(|&delta| {
+            let diff = delta as f64 - mean;
+            diff * diff
+        })
+        .sum::<f64>()
+        / count as f64;
+    let std_dev = variance.sqrt();
+
+    let histogram = build_histogram(&sorted, bins);
+
+    Stats {
+        count,
+        mean,
+        median,
+        mode,
+        std_dev,
+        histogram,
+    }
+}
+
+/// Buckets pre-sorted `deltas` into `bins` equal-width bins starting at `deltas`' min and
+/// covering min..=max. Bins are at least 1 wide, so when max - min is smaller than `bins` the
+/// trailing bins start past max and stay empty. `bins` is clamped to at least 1, since 0 would underflow the bin-index bound below.
+fn build_histogram(sorted_deltas: &[usize], bins: usize) -> Vec<(usize, usize)> {
+    let bins = bins.max(1);
+    let min = *sorted_deltas.first().unwrap();
+    let max = *sorted_deltas.last().unwrap();
+    let bin_width = ((max - min) as f64 / bins as f64).max(1.0);
+
+    let mut counts = vec![0usize; bins];
+    for &delta in sorted_deltas {
+        let bin = (((delta - min) as f64 / bin_width) as usize).min(bins - 1);
+        counts[bin] += 1;
+    }
+
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, freq)| (min + (i as f64 * bin_width).round() as usize, freq))
+        .collect()
+}
+
+/// Globs `config.input_dir` for `.png` files, pairs each with its same-named mask in
+/// `config.mask_dir` (if any), measures it, optionally writes a QC overlay under
+/// `config.overlay_dir`, and returns one [`ImageResult`] per image.
+pub fn run_batch(config: &Config) -> Result<Vec<ImageResult>> {
+    let pattern = config.input_dir.join("*.png");
+    let pattern = pattern.to_string_lossy().to_string();
+
+    let mut results = Vec::new();
+    for entry in glob::glob(&pattern)? {
+        let image_path = entry?;
+        let file_name = image_path
+            .file_name()
+            .ok_or_else(|| Error::from(format!("Not a file: {}", image_path.display())))?;
+        let mask_path = config
+            .mask_dir
+            .as_ref()
+            .map(|dir| dir.join(file_name))
+            .filter(|candidate| candidate.exists());
+
+        let deltas = get_px_deltas_from_lines(
+            image_path.to_string_lossy().to_string(),
+            mask_path.clone().map(|p| p.to_string_lossy().to_string()),
+            Some(config.scan_directions.clone()),
+            None,
+            None,
+            Some(MeasurementMode::RowScan),
+        )?;
+        let stats = compute_stats(&deltas, config.histogram_bins);
+
+        if let Some(overlay_dir) = &config.overlay_dir {
+            write_overlay(overlay_dir, &image_path, mask_path.as_deref(), file_name)?;
+        }
+
+        results.push(ImageResult {
+            image_path,
+            deltas,
+            stats,
+        });
+    }
+
+    Ok(results)
+}
+
+/// Measures `image_path`/`mask_path` row by row with their positions kept, then renders the QC
+/// overlay PNG for that image into `overlay_dir` under `file_name`.
+fn write_overlay(
+    overlay_dir: &Path,
+    image_path: &Path,
+    mask_path: Option<&Path>,
+    file_name: &std::ffi::OsStr,
+) -> Result<()> {
+    fs::create_dir_all(overlay_dir)?;
+
+    let image = load_image(image_path, None, None)?;
+    let mask = match mask_path {
+        Some(pth) => load_image(pth, None, None)?,
+        None => image.clone().mapv(|_| false),
+    };
+    let measurements = measurements_from_image(&image, &mask)?;
+
+    let out_path = overlay_dir.join(file_name);
+    render_overlay(
+        image_path.to_string_lossy().to_string(),
+        mask_path.map(|p| p.to_string_lossy().to_string()),
+        &measurements,
+        out_path.to_string_lossy().to_string(),
+    )?;
+
+    Ok(())
+}
+
+/// Writes `results` to `config.output_path` in the format(s) `config.output_format` selects
+/// (the extension is replaced with `.csv`/`.json` as needed so both can be written side by
+/// side).
+pub fn write_results(config: &Config, results: &[ImageResult]) -> Result<()> {
+    if matches!(
+        config.output_format,
+        OutputFormat::Csv | OutputFormat::Both
+    ) {
+        write_csv(&config.output_path.with_extension("csv"), results)?;
+    }
+    if matches!(
+        config.output_format,
+        OutputFormat::Json | OutputFormat::Both
+    ) {
+        write_json(&config.output_path.with_extension("json"), results)?;
+    }
+
+    Ok(())
+}
+
+fn write_csv(path: &Path, results: &[ImageResult]) -> Result<()> {
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record(["image", "count", "mean", "median", "mode", "std_dev"])?;
+    for result in results {
+        writer.write_record([
+            result.image_path.display().to_string(),
+            result.stats.count.to_string(),
+            result.stats.mean.to_string(),
+            result.stats.median.to_string(),
+            result.stats.mode.to_string(),
+            result.stats.std_dev.to_string(),
+        ])?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn write_json(path: &Path, results: &[ImageResult]) -> Result<()> {
+    let file = fs::File::create(path)?;
+    serde_json::to_writer_pretty(file, results)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    mod test_compute_stats {
+        use super::super::*;
+
+        #[test]
+        fn empty_input_has_zeroed_stats() {
+            let stats = compute_stats(&[], 10);
+            assert_eq!(stats.count, 0);
+            assert!(stats.histogram.is_empty());
+        }
+
+        #[test]
+        fn basic_moments() {
+            let deltas = [2, 3, 2, 3, 2];
+            let stats = compute_stats(&deltas, 2);
+            assert_eq!(stats.count, 5);
+            assert_eq!(stats.mean, 2.4);
+            assert_eq!(stats.median, 2.0);
+            assert_eq!(stats.mode, 2);
+        }
+
+        #[test]
+        fn histogram_bins_span_min_to_max() {
+            let deltas = [2, 3, 2, 3, 2];
+            let stats = compute_stats(&deltas, 2);
+            let total: usize = stats.histogram.iter().map(|&(_, freq)| freq).sum();
+            assert_eq!(total, deltas.len());
+        }
+
+        #[test]
+        fn zero_bins_does_not_panic() {
+            let deltas = [2, 3, 2, 3, 2];
+            let stats = compute_stats(&deltas, 0);
+            assert_eq!(stats.histogram.len(), 1);
+        }
+    }
+}
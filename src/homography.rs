@@ -0,0 +1,59 @@
+//! The projective homography solver shared by the perspective-dewarping paths in `main.rs`
+//! (the standalone CLI / batch pipeline) and `processing.rs` (the Python bindings), so the two
+//! implementations don't drift apart from maintaining separate copies.
+
+/// A row-major 3x3 homography matrix.
+pub type Mat3 = [[f64; 3]; 3];
+
+/// Solves the 8-DOF projective homography mapping each `src` point to the corresponding `dst`
+/// point, via Gaussian elimination on the linearized correspondence equations.
+pub fn solve_homography(src: &[(f64, f64); 4], dst: &[(f64, f64); 4]) -> Mat3 {
+    let mut a = [[0.0_f64; 9]; 8];
+    for i in 0..4 {
+        let (x, y) = src[i];
+        let (u, v) = dst[i];
+        a[2 * i] = [x, y, 1.0, 0.0, 0.0, 0.0, -x * u, -y * u, u];
+        a[2 * i + 1] = [0.0, 0.0, 0.0, x, y, 1.0, -x * v, -y * v, v];
+    }
+    let h = solve_linear_system(a);
+    [[h[0], h[1], h[2]], [h[3], h[4], h[5]], [h[6], h[7], 1.0]]
+}
+
+/// Gaussian elimination with partial pivoting on an 8x9 augmented matrix, solving for the 8
+/// unknown homography coefficients (the ninth column of each row holds the right-hand side).
+fn solve_linear_system(mut a: [[f64; 9]; 8]) -> [f64; 8] {
+    for col in 0..8 {
+        let pivot = (col..8)
+            .max_by(|&r1, &r2| a[r1][col].abs().partial_cmp(&a[r2][col].abs()).unwrap())
+            .unwrap();
+        a.swap(col, pivot);
+        let pivot_val = a[col][col];
+        for v in a[col][col..].iter_mut() {
+            *v /= pivot_val;
+        }
+        let pivot_row = a[col];
+        for (row, row_slice) in a.iter_mut().enumerate() {
+            if row != col {
+                let factor = row_slice[col];
+                for (c, v) in row_slice.iter_mut().enumerate().skip(col) {
+                    *v -= factor * pivot_row[c];
+                }
+            }
+        }
+    }
+
+    let mut h = [0.0; 8];
+    for (i, row) in a.iter().enumerate() {
+        h[i] = row[8];
+    }
+    h
+}
+
+/// Applies homography `h` to point `(x, y)`, returning the mapped point in Cartesian
+/// coordinates.
+pub fn apply_homography(h: &Mat3, x: f64, y: f64) -> (f64, f64) {
+    let w = h[2][0] * x + h[2][1] * y + h[2][2];
+    let u = (h[0][0] * x + h[0][1] * y + h[0][2]) / w;
+    let v = (h[1][0] * x + h[1][1] * y + h[1][2]) / w;
+    (u, v)
+}
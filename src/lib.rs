@@ -1,12 +1,44 @@
+pub mod homography;
 mod processing;
 
 use pyo3::prelude::*;
 
 /// Gets pixel deltas from lines... only faster
 #[pyfunction]
-#[pyo3(signature = (image_path, mask_path=None))]
-fn using_rust(image_path: String, mask_path: Option<String>) -> PyResult<Vec<usize>> {
-    processing::get_px_deltas_from_lines(image_path, mask_path)
+#[pyo3(signature = (image_path=None, mask_path=None, detect_edges=false, sigma=1.0, low_threshold=50.0, high_threshold=100.0, threshold=u8::MAX, directions=None, dewarp_corners=None, dewarp_out_size=None, fast_marching=false, polylines=None, polyline_size=None, stroke_width=1.0))]
+#[allow(clippy::too_many_arguments)]
+fn using_rust(
+    image_path: Option<String>,
+    mask_path: Option<String>,
+    detect_edges: bool,
+    sigma: f64,
+    low_threshold: f64,
+    high_threshold: f64,
+    threshold: u8,
+    directions: Option<Vec<f64>>,
+    dewarp_corners: Option<Vec<(f64, f64)>>,
+    dewarp_out_size: Option<(usize, usize)>,
+    fast_marching: bool,
+    polylines: Option<Vec<Vec<(f64, f64)>>>,
+    polyline_size: Option<(usize, usize)>,
+    stroke_width: f64,
+) -> PyResult<Vec<usize>> {
+    processing::get_px_deltas_from_lines(
+        image_path,
+        mask_path,
+        detect_edges,
+        sigma,
+        low_threshold,
+        high_threshold,
+        threshold,
+        directions,
+        dewarp_corners,
+        dewarp_out_size,
+        fast_marching,
+        polylines,
+        polyline_size,
+        stroke_width,
+    )
 }
 
 #[pymodule]
@@ -1,3 +1,6 @@
+mod batch;
+
+use get_px_deltas_from_lines::homography::{apply_homography, solve_homography};
 use ndarray::{Array2, Axis, Slice};
 use std::path::Path;
 
@@ -5,7 +8,32 @@ type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
 type Result<T, E = Error> = std::result::Result<T, E>;
 
 fn main() {
-    println!("Hello, world!");
+    if let Err(err) = run() {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    }
+}
+
+/// Batch mode entry point: reads the `.toml` config path from the first CLI argument, measures
+/// every foil it describes, and writes the results out in the configured format(s).
+fn run() -> Result<()> {
+    let config_path = std::env::args()
+        .nth(1)
+        .ok_or_else(|| Error::from("Usage: gpdfl <config.toml>"))?;
+    let config = batch::load_config(Path::new(&config_path))?;
+    let results = batch::run_batch(&config)?;
+    batch::write_results(&config, &results)?;
+    Ok(())
+}
+
+/// A direction to scan a grid for cell boundaries: rows, columns, or either diagonal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanDirection {
+    Horizontal,
+    Vertical,
+    DiagonalDown,
+    DiagonalUp,
 }
 
 fn validate_image_path(img_path: &Path) -> Result<()> {
@@ -21,7 +49,11 @@ fn validate_image_path(img_path: &Path) -> Result<()> {
     Ok(())
 }
 
-fn load_image(img_path: &Path) -> Result<Array2<bool>> {
+fn load_image(
+    img_path: &Path,
+    corners: Option<[(f64, f64); 4]>,
+    out_size: Option<(usize, usize)>,
+) -> Result<Array2<bool>> {
     let img_base = image::open(img_path)?.to_luma8();
     let img_vec = img_base.as_raw();
     let img_width = img_base.width() as usize;
@@ -29,7 +61,325 @@ fn load_image(img_path: &Path) -> Result<Array2<bool>> {
     let image = Array2::<u8>::from_shape_vec((img_height, img_width), img_vec.to_owned())?
         .mapv(|a| a == u8::MAX);
 
-    Ok(image)
+    match corners {
+        Some(corners) => {
+            let out_size = out_size.unwrap_or((img_height, img_width));
+            Ok(dewarp(image, corners, out_size))
+        }
+        None => Ok(image),
+    }
+}
+
+/// Warps the quadrilateral described by `corners` (top-left, top-right, bottom-right,
+/// bottom-left) into an axis-aligned `out_size` = `(height, width)` rectangle, via a projective
+/// homography sampled with nearest-neighbor (the image is already binarized).
+pub fn dewarp(image: Array2<bool>, corners: [(f64, f64); 4], out_size: (usize, usize)) -> Array2<bool> {
+    let (out_height, out_width) = out_size;
+    let dst_corners = [
+        (0.0, 0.0),
+        (out_width as f64 - 1.0, 0.0),
+        (out_width as f64 - 1.0, out_height as f64 - 1.0),
+        (0.0, out_height as f64 - 1.0),
+    ];
+    // map destination-rectangle coordinates back to the source quadrilateral so each output
+    // pixel can be sampled directly, rather than scattering source pixels into the output
+    let inverse = solve_homography(&dst_corners, &corners);
+
+    let (img_height, img_width) = (image.shape()[0], image.shape()[1]);
+    let mut out = Array2::<bool>::from_elem((out_height, out_width), false);
+    for row in 0..out_height {
+        for col in 0..out_width {
+            let (src_col, src_row) = apply_homography(&inverse, col as f64, row as f64);
+            let src_col = src_col.round();
+            let src_row = src_row.round();
+            if src_col >= 0.0
+                && src_row >= 0.0
+                && (src_col as usize) < img_width
+                && (src_row as usize) < img_height
+            {
+                out[[row, col]] = image[[src_row as usize, src_col as usize]];
+            }
+        }
+    }
+
+    out
+}
+
+/// A point in an image's `(row, col)` coordinate space, possibly fractional (contour crossings
+/// land on pixel-edge midpoints).
+type Point = (f64, f64);
+
+/// The four edges of a marching-squares cell, named by compass direction.
+#[derive(Clone, Copy)]
+enum Edge {
+    N,
+    S,
+    E,
+    W,
+}
+
+/// The midpoint of `edge` for the cell whose top-left corner is `(row, col)`.
+fn edge_point(edge: Edge, row: usize, col: usize) -> Point {
+    let (r, c) = (row as f64, col as f64);
+    match edge {
+        Edge::N => (r, c + 0.5),
+        Edge::S => (r + 1.0, c + 0.5),
+        Edge::W => (r + 0.5, c),
+        Edge::E => (r + 0.5, c + 1.0),
+    }
+}
+
+/// The edge pair(s) a marching-squares `case` (a 4-bit index built as
+/// `8*NW + 4*NE + 2*SE + 1*SW`) crosses. The ambiguous saddles (5 and 10) always connect the
+/// lower-left and upper-right edges.
+fn case_edges(case: u8) -> &'static [(Edge, Edge)] {
+    use Edge::*;
+    match case {
+        0 | 15 => &[],
+        1 | 14 => &[(W, S)],
+        2 | 13 => &[(S, E)],
+        3 | 12 => &[(W, E)],
+        4 | 11 => &[(N, E)],
+        6 | 9 => &[(N, S)],
+        7 | 8 => &[(N, W)],
+        5 | 10 => &[(N, E), (S, W)],
+        _ => unreachable!("marching squares case must be 0..=15"),
+    }
+}
+
+/// Slides a 2x2 window over `grid` and, for each cell, looks up the line segment(s) crossing it
+/// via marching squares.
+fn marching_squares_segments(grid: &Array2<bool>) -> Vec<(Point, Point)> {
+    let (height, width) = (grid.shape()[0], grid.shape()[1]);
+    let mut segments = Vec::new();
+    if height < 2 || width < 2 {
+        return segments;
+    }
+
+    for row in 0..height - 1 {
+        for col in 0..width - 1 {
+            let nw = grid[[row, col]];
+            let ne = grid[[row, col + 1]];
+            let se = grid[[row + 1, col + 1]];
+            let sw = grid[[row + 1, col]];
+            let case = (nw as u8) * 8 + (ne as u8) * 4 + (se as u8) * 2 + (sw as u8);
+            for &(a, b) in case_edges(case) {
+                segments.push((edge_point(a, row, col), edge_point(b, row, col)));
+            }
+        }
+    }
+
+    segments
+}
+
+/// A hashable key for a `Point`, exploiting that every marching-squares crossing lands on a
+/// half-integer coordinate so equality can be checked exactly rather than with an epsilon.
+fn point_key(point: Point) -> (i64, i64) {
+    ((point.0 * 2.0).round() as i64, (point.1 * 2.0).round() as i64)
+}
+
+/// Stitches loose segments sharing an endpoint into polylines. Each crossing point belongs to at
+/// most two segments (the cells on either side of it), so this is a simple walk rather than a
+/// general graph traversal.
+fn stitch_segments(segments: Vec<(Point, Point)>) -> Vec<Vec<Point>> {
+    let mut adjacency: std::collections::HashMap<(i64, i64), Vec<usize>> =
+        std::collections::HashMap::new();
+    for (idx, &(a, b)) in segments.iter().enumerate() {
+        adjacency.entry(point_key(a)).or_default().push(idx);
+        adjacency.entry(point_key(b)).or_default().push(idx);
+    }
+
+    let mut used = vec![false; segments.len()];
+    let mut polylines = Vec::new();
+    for start in 0..segments.len() {
+        if used[start] {
+            continue;
+        }
+        used[start] = true;
+        let (a, b) = segments[start];
+        let mut polyline = vec![a, b];
+
+        // grow from the tail, then reverse and grow again so both ends of the original segment
+        // get extended, regardless of which end happens to have a neighbor first; reverse back
+        // afterwards so the polyline still reads start-to-end in the original segment's direction
+        grow_polyline(&mut polyline, &segments, &adjacency, &mut used);
+        polyline.reverse();
+        grow_polyline(&mut polyline, &segments, &adjacency, &mut used);
+        polyline.reverse();
+
+        polylines.push(polyline);
+    }
+
+    polylines
+}
+
+/// Repeatedly extends `polyline` from its current tail by following an unused segment sharing
+/// that endpoint, stopping once no such segment remains.
+fn grow_polyline(
+    polyline: &mut Vec<Point>,
+    segments: &[(Point, Point)],
+    adjacency: &std::collections::HashMap<(i64, i64), Vec<usize>>,
+    used: &mut [bool],
+) {
+    loop {
+        let tail = *polyline.last().unwrap();
+        let key = point_key(tail);
+        let next = adjacency
+            .get(&key)
+            .into_iter()
+            .flatten()
+            .find(|&&idx| !used[idx])
+            .copied();
+        match next {
+            Some(idx) => {
+                used[idx] = true;
+                let (a, b) = segments[idx];
+                polyline.push(if point_key(a) == key { b } else { a });
+            }
+            None => break,
+        }
+    }
+}
+
+/// Traces the cell-boundary network in `grid` as connected polylines, via marching squares over
+/// every 2x2 window followed by stitching segments that share an endpoint.
+pub fn extract_contours(grid: &Array2<bool>) -> Vec<Vec<Point>> {
+    stitch_segments(marching_squares_segments(grid))
+}
+
+/// Measures cell widths as the pixel distance between consecutive boundary crossings along each
+/// horizontal scan line of `grid`'s traced contour network, which is more robust to single-pixel
+/// gaps in the boundary than the adjacent-pixel heuristic in [`get_diffs_from_sub_row`]. See
+/// [`MeasurementMode::ContourScan`].
+pub fn contour_deltas(grid: &Array2<bool>) -> Vec<usize> {
+    let polylines = extract_contours(grid);
+    let height = grid.shape()[0];
+
+    let mut deltas = Vec::new();
+    for row in 0..height {
+        let scan_row = row as f64;
+        let mut crossings: Vec<i64> = polylines
+            .iter()
+            .flatten()
+            .filter(|&&(r, _)| r == scan_row)
+            .map(|&(_, c)| (c * 2.0).round() as i64)
+            .collect();
+        crossings.sort_unstable();
+        crossings.dedup();
+
+        for pair in crossings.windows(2) {
+            let diff = ((pair[1] - pair[0]) / 2) as usize;
+            if diff > 0 {
+                deltas.push(diff);
+            }
+        }
+    }
+
+    deltas
+}
+
+/// A finite stand-in for "infinity" in the distance transform, so [`edt_1d`]'s lower-envelope
+/// arithmetic never produces NaN from an inf-minus-inf.
+const EDT_UNREACHED: f64 = 1e20;
+
+/// Computes the squared Euclidean distance transform of `grid`, treating `true` (boundary)
+/// pixels as sources at distance 0. Uses the Felzenszwalb-Huttenlocher two-pass separable
+/// algorithm: a 1-D transform along every row, then along every column of the result.
+pub fn squared_edt(grid: &Array2<bool>) -> Array2<f64> {
+    let (height, width) = (grid.shape()[0], grid.shape()[1]);
+    let mut field = Array2::<f64>::from_elem((height, width), EDT_UNREACHED);
+    for ((r, c), &is_edge) in grid.indexed_iter() {
+        if is_edge {
+            field[[r, c]] = 0.0;
+        }
+    }
+
+    for r in 0..height {
+        let row: Vec<f64> = (0..width).map(|c| field[[r, c]]).collect();
+        let transformed = edt_1d(&row);
+        for (c, value) in transformed.into_iter().enumerate() {
+            field[[r, c]] = value;
+        }
+    }
+
+    for c in 0..width {
+        let col: Vec<f64> = (0..height).map(|r| field[[r, c]]).collect();
+        let transformed = edt_1d(&col);
+        for (r, value) in transformed.into_iter().enumerate() {
+            field[[r, c]] = value;
+        }
+    }
+
+    field
+}
+
+/// The 1-D squared-distance lower-envelope transform: for each position `q`, finds
+/// `min_p (q - p)^2 + f[p]` in O(n) by sweeping the lower envelope of the parabolas rooted at
+/// each `f[p]`, using a stack of the envelope's vertices (`v`) and the abscissae where
+/// consecutive parabolas intersect (`z`).
+fn edt_1d(f: &[f64]) -> Vec<f64> {
+    let n = f.len();
+    let mut d = vec![0.0; n];
+    let mut v = vec![0usize; n];
+    let mut z = vec![0.0; n + 1];
+    let mut k = 0usize;
+    z[0] = f64::NEG_INFINITY;
+    z[1] = f64::INFINITY;
+
+    for q in 1..n {
+        let mut s;
+        loop {
+            s = parabola_intersection(f, v[k], q);
+            if s <= z[k] {
+                if k == 0 {
+                    break;
+                }
+                k -= 1;
+            } else {
+                break;
+            }
+        }
+        k += 1;
+        v[k] = q;
+        z[k] = s;
+        z[k + 1] = f64::INFINITY;
+    }
+
+    let mut k = 0usize;
+    for (q, slot) in d.iter_mut().enumerate() {
+        while z[k + 1] < q as f64 {
+            k += 1;
+        }
+        let dx = q as f64 - v[k] as f64;
+        *slot = dx * dx + f[v[k]];
+    }
+
+    d
+}
+
+/// The abscissa where the parabolas rooted at `f[p]` and `f[q]` intersect.
+fn parabola_intersection(f: &[f64], p: usize, q: usize) -> f64 {
+    let (pf, qf) = (p as f64, q as f64);
+    ((f[q] + qf * qf) - (f[p] + pf * pf)) / (2.0 * qf - 2.0 * pf)
+}
+
+/// Estimates cell widths from the ridge of `grid`'s squared Euclidean distance transform, which
+/// is robust to boundaries that are broken in places. The ridge sits on the medial axis between
+/// two boundaries at half the local cell width, so the reported delta is twice that distance.
+pub fn medial_axis_deltas(grid: &Array2<bool>) -> Vec<usize> {
+    let dist = squared_edt(grid).mapv(f64::sqrt);
+    let (height, width) = (dist.shape()[0], dist.shape()[1]);
+    let mut deltas = Vec::new();
+    for r in 0..height {
+        for c in 1..width.saturating_sub(1) {
+            let center = dist[[r, c]];
+            if center > 0.0 && center > dist[[r, c - 1]] && center > dist[[r, c + 1]] {
+                deltas.push((2.0 * center).round() as usize);
+            }
+        }
+    }
+
+    deltas
 }
 
 fn find_true_indices(vec: &[&bool]) -> Vec<usize> {
@@ -40,33 +390,63 @@ fn find_true_indices(vec: &[&bool]) -> Vec<usize> {
         .collect::<Vec<_>>()
 }
 
+/// Which algorithm `get_px_deltas_from_lines` uses to turn a boundary image into a set of
+/// pixel deltas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MeasurementMode {
+    /// The original behavior: slice the image into 1-D lines per [`ScanDirection`] and measure
+    /// gaps between boundary pixels along each line.
+    RowScan,
+    /// Measure cell widths from the ridge of a squared Euclidean distance transform, which is
+    /// robust to broken traces. See [`medial_axis_deltas`].
+    MedialAxis,
+    /// Measure cell widths between consecutive crossings of the traced contour network along
+    /// each row, rather than between adjacent boundary pixels. See [`contour_deltas`].
+    ContourScan,
+}
+
 /// the main one todo: wrap
 pub fn get_px_deltas_from_lines(
     image_path: String,
     mask_path: Option<String>,
+    directions: Option<Vec<ScanDirection>>,
+    dewarp_corners: Option<[(f64, f64); 4]>,
+    dewarp_out_size: Option<(usize, usize)>,
+    mode: Option<MeasurementMode>,
 ) -> Result<Vec<usize>> {
     let image_path = Path::new(&image_path);
     validate_image_path(image_path)?;
-    let image = load_image(image_path)?;
+    let image = load_image(image_path, dewarp_corners, dewarp_out_size)?;
 
     let mask = match mask_path {
         Some(pth) => {
             let mask_path = Path::new(&pth);
             validate_image_path(mask_path)?;
-            load_image(image_path)?
+            load_image(mask_path, dewarp_corners, dewarp_out_size)?
         }
         // no mask
         None => image.clone().mapv(|_| false),
     };
 
-    let result = get_all_diffs(image, mask)?;
+    let result = match mode.unwrap_or(MeasurementMode::RowScan) {
+        MeasurementMode::RowScan => {
+            let directions = directions.unwrap_or_else(|| vec![ScanDirection::Horizontal]);
+            get_all_diffs(image, mask, &directions)?
+        }
+        MeasurementMode::MedialAxis => medial_axis_deltas(&image),
+        MeasurementMode::ContourScan => contour_deltas(&image),
+    };
 
     Ok(result)
 }
 
-/// Gets all distances between cell edges within a single image. A mask is required, but may be
-/// all false (i.e. no masking).
-fn get_all_diffs(image: Array2<bool>, mask: Array2<bool>) -> Result<Vec<usize>> {
+/// Gets all distances between cell edges within a single image, scanning along each requested
+/// direction and concatenating the results. A mask is required, but may be all false.
+fn get_all_diffs(
+    image: Array2<bool>,
+    mask: Array2<bool>,
+    directions: &[ScanDirection],
+) -> Result<Vec<usize>> {
     if image.shape() != mask.shape() {
         let msg = format!(
             "Shape mismatch: img={:?}, mask={:?}",
@@ -76,27 +456,116 @@ fn get_all_diffs(image: Array2<bool>, mask: Array2<bool>) -> Result<Vec<usize>>
         return Err(Error::from(msg));
     }
 
-    let axis = Axis(0);
     let mut diffs = Vec::new();
-    let img_height = image.shape()[0] as isize;
-    for row in 0..img_height {
-        // take the whole row
-        let indices = Slice::new(row, Some(row + 1), 1);
-        let row = image
+    for direction in directions {
+        let mut dir_diffs = match direction {
+            ScanDirection::Horizontal => get_diffs_along_axis(&image, &mask, Axis(0))?,
+            ScanDirection::Vertical => get_diffs_along_axis(&image, &mask, Axis(1))?,
+            ScanDirection::DiagonalDown => get_diffs_along_diagonals(&image, &mask, true)?,
+            ScanDirection::DiagonalUp => get_diffs_along_diagonals(&image, &mask, false)?,
+        };
+        diffs.append(&mut dir_diffs);
+    }
+
+    Ok(diffs)
+}
+
+/// Gets all distances between cell edges by slicing `image`/`mask` one line at a time along
+/// `axis` (`Axis(0)` for rows, `Axis(1)` for columns).
+fn get_diffs_along_axis(image: &Array2<bool>, mask: &Array2<bool>, axis: Axis) -> Result<Vec<usize>> {
+    let mut diffs = Vec::new();
+    let line_count = image.shape()[axis.index()] as isize;
+    for line in 0..line_count {
+        let indices = Slice::new(line, Some(line + 1), 1);
+        let line_img = image
             .slice_axis(axis, indices)
             .into_iter()
             .collect::<Vec<&bool>>();
-        let row_mask = mask
+        let line_mask = mask
             .slice_axis(axis, indices)
             .into_iter()
             .collect::<Vec<&bool>>();
-        let mut row_diffs = get_diffs_from_row(row, row_mask)?;
-        diffs.append(&mut row_diffs);
+        let mut line_diffs = get_diffs_from_row(line_img, line_mask)?;
+        diffs.append(&mut line_diffs);
+    }
+
+    Ok(diffs)
+}
+
+/// Gets all distances between cell edges along every main diagonal (`descending`) or
+/// anti-diagonal (`!descending`) of `image`/`mask`.
+fn get_diffs_along_diagonals(
+    image: &Array2<bool>,
+    mask: &Array2<bool>,
+    descending: bool,
+) -> Result<Vec<usize>> {
+    let (height, width) = (image.shape()[0], image.shape()[1]);
+    let mut diffs = Vec::new();
+    for coords in diagonal_coords(height, width, descending) {
+        if coords.len() < 2 {
+            continue;
+        }
+        let line: Vec<&bool> = coords.iter().map(|&(r, c)| &image[[r, c]]).collect();
+        let line_mask: Vec<&bool> = coords.iter().map(|&(r, c)| &mask[[r, c]]).collect();
+        let mut line_diffs = get_diffs_from_row(line, line_mask)?;
+        diffs.append(&mut line_diffs);
     }
 
     Ok(diffs)
 }
 
+/// Gathers the `(row, col)` index sequence of every diagonal (or anti-diagonal) of a
+/// `height` x `width` grid.
+fn diagonal_coords(height: usize, width: usize, descending: bool) -> Vec<Vec<(usize, usize)>> {
+    if height == 0 || width == 0 {
+        return Vec::new();
+    }
+
+    let mut starts = Vec::new();
+    if descending {
+        starts.extend((0..height).map(|row| (row, 0)));
+        starts.extend((1..width).map(|col| (0, col)));
+    } else {
+        starts.extend((0..height).map(|row| (row, width - 1)));
+        starts.extend((0..width - 1).map(|col| (0, col)));
+    }
+
+    starts
+        .into_iter()
+        .map(|(row, col)| trace_diagonal(row, col, height, width, descending))
+        .collect()
+}
+
+/// Walks a single diagonal starting at `(row, col)`, stepping `(+1, +1)` when `descending` or
+/// `(+1, -1)` otherwise, until the walk would leave the `height` x `width` grid.
+fn trace_diagonal(
+    mut row: usize,
+    mut col: usize,
+    height: usize,
+    width: usize,
+    descending: bool,
+) -> Vec<(usize, usize)> {
+    let mut coords = vec![(row, col)];
+    loop {
+        if descending {
+            if row + 1 >= height || col + 1 >= width {
+                break;
+            }
+            row += 1;
+            col += 1;
+        } else {
+            if row + 1 >= height || col == 0 {
+                break;
+            }
+            row += 1;
+            col -= 1;
+        }
+        coords.push((row, col));
+    }
+
+    coords
+}
+
 /// Get all pixel distances between cell boundaries for a single row in an image
 fn get_diffs_from_row(row: Vec<&bool>, row_mask: Vec<&bool>) -> Result<Vec<usize>> {
     // find indices to split row into sub-rows
@@ -145,6 +614,167 @@ fn get_diffs_from_sub_row(sub_row: &[&bool]) -> Result<Vec<usize>> {
     Ok(diffs)
 }
 
+/// One accepted measurement from a horizontal row scan: the pixel distance between two
+/// boundary crossings, plus where that pair sits in the original image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Measurement {
+    pub row: usize,
+    pub start_col: usize,
+    pub end_col: usize,
+}
+
+impl Measurement {
+    pub fn delta(&self) -> usize {
+        self.end_col - self.start_col
+    }
+}
+
+/// Scans `image`/`mask` row by row, same as [`get_all_diffs`] with [`ScanDirection::Horizontal`],
+/// but keeping each accepted measurement's row/column position instead of collapsing it to a
+/// delta length.
+pub fn measurements_from_image(image: &Array2<bool>, mask: &Array2<bool>) -> Result<Vec<Measurement>> {
+    if image.shape() != mask.shape() {
+        let msg = format!(
+            "Shape mismatch: img={:?}, mask={:?}",
+            image.shape(),
+            mask.shape()
+        );
+        return Err(Error::from(msg));
+    }
+
+    let axis = Axis(0);
+    let mut measurements = Vec::new();
+    let img_height = image.shape()[0] as isize;
+    for row in 0..img_height {
+        let indices = Slice::new(row, Some(row + 1), 1);
+        let row_img = image
+            .slice_axis(axis, indices)
+            .into_iter()
+            .collect::<Vec<&bool>>();
+        let row_mask = mask
+            .slice_axis(axis, indices)
+            .into_iter()
+            .collect::<Vec<&bool>>();
+        measurements.extend(measurements_from_row(row as usize, row_img, row_mask));
+    }
+
+    Ok(measurements)
+}
+
+/// Same traversal as [`get_diffs_from_row`], but keeping the row index and each measurement's
+/// start/end columns instead of collapsing straight to a delta length.
+fn measurements_from_row(row_idx: usize, row: Vec<&bool>, row_mask: Vec<&bool>) -> Vec<Measurement> {
+    let mut mask_split_indices = find_true_indices(row_mask.as_slice());
+    mask_split_indices.push(row.len());
+
+    let mut idx_start = 0;
+    let mut measurements = Vec::new();
+    for idx_end in mask_split_indices {
+        if (idx_end == 0) || !row_mask[idx_end - 1].to_owned() {
+            let split = &row.as_slice()[idx_start..idx_end];
+            measurements.extend(measurements_from_sub_row(row_idx, idx_start, split));
+        }
+        idx_start = idx_end + 1;
+    }
+
+    measurements
+}
+
+/// Same edge-finding logic as [`get_diffs_from_sub_row`], but reporting each accepted
+/// measurement's absolute row/column position (`col_offset` is the sub-row's starting column
+/// within the full row) instead of only its length.
+fn measurements_from_sub_row(row_idx: usize, col_offset: usize, sub_row: &[&bool]) -> Vec<Measurement> {
+    let edges = find_true_indices(sub_row);
+    let mut measurements = Vec::new();
+    let mut last_edge_idx = 0;
+    for idx in edges {
+        if idx != last_edge_idx {
+            let diff = idx - last_edge_idx;
+            if diff > 1 {
+                measurements.push(Measurement {
+                    row: row_idx,
+                    start_col: col_offset + last_edge_idx,
+                    end_col: col_offset + idx,
+                });
+            }
+        }
+        last_edge_idx = idx;
+    }
+
+    measurements
+}
+
+/// Draws each measured segment from `measurements` over the image at `image_path`, optionally
+/// also highlighting the stretches excluded by `mask_path`, and writes the result as a PNG at
+/// `out_path`.
+pub fn render_overlay(
+    image_path: String,
+    mask_path: Option<String>,
+    measurements: &[Measurement],
+    out_path: String,
+) -> Result<()> {
+    const MEASUREMENT_COLOR: image::Rgba<u8> = image::Rgba([255, 0, 0, 255]);
+    const MASK_COLOR: image::Rgba<u8> = image::Rgba([0, 128, 255, 255]);
+
+    let image_path = Path::new(&image_path);
+    validate_image_path(image_path)?;
+    let mut canvas = image::open(image_path)?.to_rgba8();
+
+    for measurement in measurements {
+        draw_line(
+            &mut canvas,
+            (measurement.start_col as i64, measurement.row as i64),
+            (measurement.end_col as i64, measurement.row as i64),
+            MEASUREMENT_COLOR,
+        );
+    }
+
+    if let Some(pth) = mask_path {
+        let mask_path = Path::new(&pth);
+        validate_image_path(mask_path)?;
+        let mask = load_image(mask_path, None, None)?;
+        for ((r, c), &is_masked) in mask.indexed_iter() {
+            if is_masked && (r as u32) < canvas.height() && (c as u32) < canvas.width() {
+                canvas.put_pixel(c as u32, r as u32, MASK_COLOR);
+            }
+        }
+    }
+
+    canvas.save(out_path)?;
+    Ok(())
+}
+
+/// Rasterizes the line from `start` to `end` into `canvas` with `color`, using Bresenham's
+/// algorithm. Points outside the canvas are skipped rather than panicking, since a measurement's
+/// column can legitimately sit at the image's right edge.
+fn draw_line(canvas: &mut image::RgbaImage, start: (i64, i64), end: (i64, i64), color: image::Rgba<u8>) {
+    let (mut x0, mut y0) = start;
+    let (x1, y1) = end;
+    let dx = (x1 - x0).abs();
+    let dy = -(y1 - y0).abs();
+    let sx = if x0 < x1 { 1 } else { -1 };
+    let sy = if y0 < y1 { 1 } else { -1 };
+    let mut err = dx + dy;
+
+    loop {
+        if x0 >= 0 && y0 >= 0 && (x0 as u32) < canvas.width() && (y0 as u32) < canvas.height() {
+            canvas.put_pixel(x0 as u32, y0 as u32, color);
+        }
+        if x0 == x1 && y0 == y1 {
+            break;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x0 += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y0 += sy;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -190,7 +820,7 @@ mod tests {
         fn grayscale() -> Result<()> {
             let img_path = Path::new("test_data/gray.png");
             let correct = ndarray::arr2(GOOD_IMG);
-            let loaded = load_image(img_path)?;
+            let loaded = load_image(img_path, None, None)?;
             assert_eq!(correct, loaded);
             Ok(())
         }
@@ -199,7 +829,7 @@ mod tests {
         fn rgb() -> Result<()> {
             let img_path = Path::new("test_data/not_gray.png");
             let correct = ndarray::arr2(GOOD_IMG);
-            let loaded = load_image(img_path)?;
+            let loaded = load_image(img_path, None, None)?;
             assert_eq!(correct, loaded);
             Ok(())
         }
@@ -208,12 +838,113 @@ mod tests {
         fn not_8_bit() -> Result<()> {
             let img_path = Path::new("test_data/not_gray_16.png");
             let correct = ndarray::arr2(GOOD_IMG);
-            let loaded = load_image(img_path)?;
+            let loaded = load_image(img_path, None, None)?;
             assert_eq!(correct, loaded);
             Ok(())
         }
     }
 
+    mod test_dewarp {
+        use super::super::*;
+
+        #[test]
+        fn identity_quad_is_a_no_op() {
+            let mut image = Array2::<bool>::from_elem((4, 4), false);
+            image[[1, 1]] = true;
+            image[[2, 2]] = true;
+            let corners = [(0.0, 0.0), (3.0, 0.0), (3.0, 3.0), (0.0, 3.0)];
+            let warped = dewarp(image.clone(), corners, (4, 4));
+            assert_eq!(warped, image);
+        }
+
+        #[test]
+        fn homography_maps_corners_correctly() {
+            // the inverse homography dewarp uses (dst -> src) should send every destination
+            // rectangle corner back to its corresponding trapezoid corner exactly
+            let dst = [(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)];
+            let src = [(1.0, 0.0), (3.0, 0.0), (4.0, 2.0), (0.0, 2.0)];
+            let h = solve_homography(&dst, &src);
+            for (i, &(dx, dy)) in dst.iter().enumerate() {
+                let (sx, sy) = apply_homography(&h, dx, dy);
+                assert!((sx - src[i].0).abs() < 1e-9);
+                assert!((sy - src[i].1).abs() < 1e-9);
+            }
+        }
+    }
+
+    mod test_extract_contours {
+        use super::super::*;
+
+        #[test]
+        fn single_segment_between_two_cells() {
+            // col 0 is all true, cols 1-2 all false: a single vertical boundary at col 0.5
+            let image = ndarray::arr2(&[[true, false, false], [true, false, false]]);
+            let contours = extract_contours(&image);
+            assert_eq!(contours.len(), 1);
+            assert_eq!(contours[0], vec![(0.0, 0.5), (1.0, 0.5)]);
+        }
+
+        #[test]
+        fn stitches_segments_across_cell_boundary() {
+            // same vertical boundary extended down a second row of cells; the two segments
+            // should stitch into a single 3-point polyline through the shared crossing
+            let image = ndarray::arr2(&[
+                [true, false, false],
+                [true, false, false],
+                [true, false, false],
+            ]);
+            let contours = extract_contours(&image);
+            assert_eq!(contours.len(), 1);
+            let polyline = &contours[0];
+            assert_eq!(polyline.len(), 3);
+            for expected in [(0.0, 0.5), (1.0, 0.5), (2.0, 0.5)] {
+                assert!(polyline.contains(&expected));
+            }
+        }
+
+        #[test]
+        fn uniform_image_has_no_contours() {
+            let image = Array2::<bool>::from_elem((4, 4), false);
+            assert!(extract_contours(&image).is_empty());
+            let image = Array2::<bool>::from_elem((4, 4), true);
+            assert!(extract_contours(&image).is_empty());
+        }
+    }
+
+    mod test_squared_edt {
+        use super::super::*;
+
+        #[test]
+        fn single_row_matches_known_distances() {
+            let grid = ndarray::arr2(&[[true, false, false, false, true]]);
+            let field = squared_edt(&grid);
+            let expected = ndarray::arr2(&[[0.0, 1.0, 4.0, 1.0, 0.0]]);
+            assert_eq!(field, expected);
+        }
+
+        #[test]
+        fn boundary_pixels_are_zero() {
+            let mut grid = Array2::<bool>::from_elem((3, 3), false);
+            grid[[1, 1]] = true;
+            let field = squared_edt(&grid);
+            assert_eq!(field[[1, 1]], 0.0);
+            assert_eq!(field[[0, 1]], 1.0);
+            assert_eq!(field[[0, 0]], 2.0);
+        }
+    }
+
+    mod test_medial_axis_deltas {
+        use super::super::*;
+
+        #[test]
+        fn ridge_midpoint_reports_the_full_gap() {
+            // edges 4 px apart; the medial-axis ridge sits 2 px from each, so 2x that is 4
+            let grid = ndarray::arr2(&[[true, false, false, false, true]]);
+            let deltas = medial_axis_deltas(&grid);
+            assert_eq!(deltas, vec![4]);
+        }
+    }
+
     #[test]
     fn test_get_diffs_from_sub_row() -> Result<()> {
         let sub_row = &[
@@ -242,6 +973,58 @@ mod tests {
         Ok(())
     }
 
+    mod test_measurements_from_image {
+        use super::super::*;
+
+        #[test]
+        fn positions_match_scalar_deltas() -> Result<()> {
+            let img_height = 2;
+            let img_width = 8;
+            let image = Vec::from([
+                [true, false, true, false, false, true, false, true], // gaps: 2, 3, 2
+                [true, false, true, false, false, true, false, true], // gaps: 2, 3, 2
+            ])
+            .concat();
+            let image = Array2::<bool>::from_shape_vec((img_height, img_width), image)?;
+            let mask = Vec::from([
+                [false, false, false, false, false, false, false, false], // keep gaps
+                [false, true, false, false, false, false, false, true], // new gaps: 3 only
+            ])
+            .concat();
+            let mask = Array2::<bool>::from_shape_vec((img_height, img_width), mask)?;
+
+            let measurements = measurements_from_image(&image, &mask)?;
+            let deltas: Vec<usize> = measurements.iter().map(Measurement::delta).collect();
+            assert_eq!(deltas, vec![2, 3, 2, 3]);
+            assert_eq!(
+                measurements[0],
+                Measurement {
+                    row: 0,
+                    start_col: 0,
+                    end_col: 2,
+                }
+            );
+            assert_eq!(
+                measurements[3],
+                Measurement {
+                    row: 1,
+                    start_col: 2,
+                    end_col: 5,
+                }
+            );
+
+            Ok(())
+        }
+
+        #[test]
+        fn test_shape_mismatch() {
+            let image = Array2::zeros((1, 4)).mapv(|a: i8| a != 0);
+            let mask = Array2::zeros((4, 59)).mapv(|a: i8| a != 0);
+            let result = measurements_from_image(&image, &mask);
+            assert!(result.is_err());
+        }
+    }
+
     mod test_get_all_diffs {
         use super::super::*;
 
@@ -262,7 +1045,7 @@ mod tests {
             .concat();
             let mask = Array2::<bool>::from_shape_vec((img_height, img_width), mask)?;
             let good = [2, 3, 2, 3];
-            let result = get_all_diffs(image, mask)?;
+            let result = get_all_diffs(image, mask, &[ScanDirection::Horizontal])?;
             assert_eq!(result, good);
 
             Ok(())
@@ -272,7 +1055,7 @@ mod tests {
         fn test_shape_mismatch() {
             let image = Array2::zeros((1, 4)).mapv(|a: i8| a != 0);
             let mask = Array2::zeros((4, 59)).mapv(|a: i8| a != 0);
-            let result = get_all_diffs(image, mask);
+            let result = get_all_diffs(image, mask, &[ScanDirection::Horizontal]);
             assert!(result.is_err());
             assert!(result
                 .err()
@@ -280,5 +1063,87 @@ mod tests {
                 .to_string()
                 .contains("Shape mismatch: img="));
         }
+
+        #[test]
+        fn test_vertical() -> Result<()> {
+            // transpose of the horizontal test_good_value case, so scanning Vertical should
+            // reproduce the same gaps
+            let img_height = 8;
+            let img_width = 2;
+            let image = Vec::from([
+                [true, true],
+                [false, false],
+                [true, true],
+                [false, false],
+                [false, false],
+                [true, true],
+                [false, false],
+                [true, true],
+            ])
+            .concat();
+            let image = Array2::<bool>::from_shape_vec((img_height, img_width), image)?;
+            let mask = Array2::<bool>::from_elem((img_height, img_width), false);
+            let good = [2, 3, 2, 2, 3, 2];
+            let result = get_all_diffs(image, mask, &[ScanDirection::Vertical])?;
+            assert_eq!(result, good);
+
+            Ok(())
+        }
+
+        #[test]
+        fn test_diagonal_down() -> Result<()> {
+            let img_size = 5;
+            let mut image = Array2::<bool>::from_elem((img_size, img_size), false);
+            // single main diagonal with edges 2 px apart: (0,0), (2,2), (4,4)
+            image[[0, 0]] = true;
+            image[[2, 2]] = true;
+            image[[4, 4]] = true;
+            let mask = Array2::<bool>::from_elem((img_size, img_size), false);
+            let result = get_all_diffs(image, mask, &[ScanDirection::DiagonalDown])?;
+            assert_eq!(result, vec![2, 2]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn test_diagonal_up() -> Result<()> {
+            let img_size = 5;
+            let mut image = Array2::<bool>::from_elem((img_size, img_size), false);
+            // single anti-diagonal with edges 2 px apart: (0,4), (2,2), (4,0)
+            image[[0, 4]] = true;
+            image[[2, 2]] = true;
+            image[[4, 0]] = true;
+            let mask = Array2::<bool>::from_elem((img_size, img_size), false);
+            let result = get_all_diffs(image, mask, &[ScanDirection::DiagonalUp])?;
+            assert_eq!(result, vec![2, 2]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn test_multiple_directions_concatenates_results() -> Result<()> {
+            // tall enough that a column can show a vertical gap too, not just a row a horizontal one
+            let img_height = 4;
+            let img_width = 8;
+            let image = Vec::from([
+                [true, false, true, false, false, true, false, true],
+                [false, false, false, false, false, false, false, false],
+                [false, false, false, false, false, false, false, false],
+                [true, false, true, false, false, true, false, true],
+            ])
+            .concat();
+            let image = Array2::<bool>::from_shape_vec((img_height, img_width), image)?;
+            let mask = Array2::<bool>::from_elem((img_height, img_width), false);
+            let horizontal_only =
+                get_all_diffs(image.clone(), mask.clone(), &[ScanDirection::Horizontal])?;
+            let both = get_all_diffs(
+                image,
+                mask,
+                &[ScanDirection::Horizontal, ScanDirection::Vertical],
+            )?;
+            assert!(both.len() > horizontal_only.len());
+
+            Ok(())
+        }
     }
 }
@@ -1,19 +1,50 @@
+use crate::homography::{apply_homography, solve_homography, Mat3};
 use ndarray::{Array2, Axis, Slice};
-use num_traits::Bounded;
 use pyo3::exceptions::{PyFileExistsError, PyRuntimeError, PyValueError};
 use pyo3::PyResult;
 use std::path::Path;
 
 /// the main function
+#[allow(clippy::too_many_arguments)]
 pub fn get_px_deltas_from_lines(
-    image_path: String,
+    image_path: Option<String>,
     mask_path: Option<String>,
+    detect_edges: bool,
+    sigma: f64,
+    low_threshold: f64,
+    high_threshold: f64,
+    threshold: u8,
+    directions: Option<Vec<f64>>,
+    dewarp_corners: Option<Vec<(f64, f64)>>,
+    dewarp_out_size: Option<(usize, usize)>,
+    fast_marching: bool,
+    polylines: Option<Vec<Vec<(f64, f64)>>>,
+    polyline_size: Option<(usize, usize)>,
+    stroke_width: f64,
 ) -> PyResult<Vec<usize>> {
-    let image_path = Path::new(&image_path);
-    validate_image_path(image_path)?;
-    let image = load_image(image_path)?;
+    let mut image = match (image_path, polylines) {
+        (Some(_), Some(_)) => {
+            return Err(PyValueError::new_err(
+                "Provide either image_path or polylines, not both",
+            ));
+        }
+        (Some(pth), None) => {
+            let image_path = Path::new(&pth);
+            validate_image_path(image_path)?;
+            load_image(image_path)?
+        }
+        (None, Some(polylines)) => {
+            let out_size = polyline_size.ok_or_else(|| {
+                PyValueError::new_err("polyline_size is required when rasterizing polylines")
+            })?;
+            rasterize_polylines(&polylines, out_size, stroke_width)
+        }
+        (None, None) => {
+            return Err(PyValueError::new_err("Provide either image_path or polylines"));
+        }
+    };
 
-    let mask = match mask_path {
+    let mut mask = match mask_path {
         Some(pth) => {
             let mask_path = Path::new(&pth);
             validate_image_path(mask_path)?;
@@ -23,11 +54,214 @@ pub fn get_px_deltas_from_lines(
         None => image.clone().mapv(|_| 0),
     };
 
-    let result = all_pixel_deltas(image, mask)?;
+    if let Some(corners) = dewarp_corners {
+        let corners = corners_from_vec(corners)?;
+        let out_size = dewarp_out_size.unwrap_or((image.shape()[0], image.shape()[1]));
+        image = warp_perspective(&image, &corners, out_size);
+        mask = warp_mask(&mask, &corners, out_size);
+    }
+
+    if detect_edges {
+        image = canny_edges(&image, sigma, low_threshold, high_threshold);
+    }
+
+    if fast_marching {
+        return Ok(fast_marching_deltas(&image, &mask, threshold));
+    }
+
+    // horizontal-only scanning is the historical default, so an absent `directions` keeps the
+    // old behavior rather than measuring every orientation
+    let directions = directions.unwrap_or_else(|| vec![0.0]);
+    let mut result = Vec::new();
+    for angle_deg in directions {
+        let mut diffs = pixel_deltas_along_angle(&image, &mask, threshold, angle_deg)?;
+        result.append(&mut diffs);
+    }
 
     Ok(result)
 }
 
+/// Converts the Python-facing `Vec<(f64, f64)>` corner list into the fixed-size quadrilateral
+/// [`warp_perspective`]/[`warp_mask`] expect, erroring out if the caller didn't supply exactly
+/// four (top-left, top-right, bottom-right, bottom-left) points.
+fn corners_from_vec(corners: Vec<(f64, f64)>) -> PyResult<[(f64, f64); 4]> {
+    corners.try_into().map_err(|corners: Vec<(f64, f64)>| {
+        PyValueError::new_err(format!(
+            "Expected 4 dewarp corners (top-left, top-right, bottom-right, bottom-left), got {}",
+            corners.len()
+        ))
+    })
+}
+
+/// Converts a grayscale `image` into a binary edge map via the Canny algorithm: a Gaussian blur
+/// of width `sigma`, then Sobel gradients, non-maximum suppression, and hysteresis thresholding
+/// between `low_threshold` and `high_threshold` on gradient magnitude.
+fn canny_edges(image: &Array2<u8>, sigma: f64, low_threshold: f64, high_threshold: f64) -> Array2<u8> {
+    let kernel = gaussian_kernel_1d(sigma);
+    let blurred = convolve_separable(image, &kernel);
+    let (gx, gy) = sobel_gradients(&blurred);
+    let suppressed = non_max_suppression(&gx, &gy);
+    hysteresis_threshold(&suppressed, low_threshold, high_threshold)
+}
+
+/// A normalized 1-D Gaussian kernel with the given standard deviation, wide enough to cover
+/// +/-3 sigma.
+fn gaussian_kernel_1d(sigma: f64) -> Vec<f64> {
+    let radius = (sigma * 3.0).ceil().max(1.0) as isize;
+    let mut kernel: Vec<f64> = (-radius..=radius)
+        .map(|x| (-(x as f64 * x as f64) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let sum: f64 = kernel.iter().sum();
+    for weight in kernel.iter_mut() {
+        *weight /= sum;
+    }
+
+    kernel
+}
+
+/// Convolves `image` with the separable `kernel` (applied along rows, then columns), clamping
+/// at the borders rather than padding with zeros so edge pixels aren't artificially darkened.
+fn convolve_separable(image: &Array2<u8>, kernel: &[f64]) -> Array2<f64> {
+    let (height, width) = (image.shape()[0], image.shape()[1]);
+    let radius = (kernel.len() / 2) as isize;
+
+    let mut horizontal = Array2::<f64>::zeros((height, width));
+    for r in 0..height {
+        for c in 0..width {
+            let mut acc = 0.0;
+            for (k, &weight) in kernel.iter().enumerate() {
+                let cc = (c as isize + k as isize - radius).clamp(0, width as isize - 1) as usize;
+                acc += weight * image[[r, cc]] as f64;
+            }
+            horizontal[[r, c]] = acc;
+        }
+    }
+
+    let mut result = Array2::<f64>::zeros((height, width));
+    for r in 0..height {
+        for c in 0..width {
+            let mut acc = 0.0;
+            for (k, &weight) in kernel.iter().enumerate() {
+                let rr = (r as isize + k as isize - radius).clamp(0, height as isize - 1) as usize;
+                acc += weight * horizontal[[rr, c]];
+            }
+            result[[r, c]] = acc;
+        }
+    }
+
+    result
+}
+
+/// Horizontal/vertical Sobel gradients of `blurred`, clamping at the borders like
+/// [`convolve_separable`].
+fn sobel_gradients(blurred: &Array2<f64>) -> (Array2<f64>, Array2<f64>) {
+    const KX: [[f64; 3]; 3] = [[-1.0, 0.0, 1.0], [-2.0, 0.0, 2.0], [-1.0, 0.0, 1.0]];
+    const KY: [[f64; 3]; 3] = [[-1.0, -2.0, -1.0], [0.0, 0.0, 0.0], [1.0, 2.0, 1.0]];
+
+    let (height, width) = (blurred.shape()[0], blurred.shape()[1]);
+    let mut gx = Array2::<f64>::zeros((height, width));
+    let mut gy = Array2::<f64>::zeros((height, width));
+    for r in 0..height {
+        for c in 0..width {
+            let mut sx = 0.0;
+            let mut sy = 0.0;
+            for dr in -1..=1isize {
+                for dc in -1..=1isize {
+                    let rr = (r as isize + dr).clamp(0, height as isize - 1) as usize;
+                    let cc = (c as isize + dc).clamp(0, width as isize - 1) as usize;
+                    let value = blurred[[rr, cc]];
+                    sx += KX[(dr + 1) as usize][(dc + 1) as usize] * value;
+                    sy += KY[(dr + 1) as usize][(dc + 1) as usize] * value;
+                }
+            }
+            gx[[r, c]] = sx;
+            gy[[r, c]] = sy;
+        }
+    }
+
+    (gx, gy)
+}
+
+/// Thins the gradient magnitude of `(gx, gy)` down to single-pixel-wide ridges: each pixel is
+/// kept only if its magnitude is >= both neighbors along the gradient direction, quantized to
+/// the nearest of 4 directions (0/45/90/135 degrees). Border pixels are dropped for lacking a
+/// full neighborhood.
+fn non_max_suppression(gx: &Array2<f64>, gy: &Array2<f64>) -> Array2<f64> {
+    let (height, width) = (gx.shape()[0], gx.shape()[1]);
+    let magnitude =
+        Array2::from_shape_fn((height, width), |(r, c)| gx[[r, c]].hypot(gy[[r, c]]));
+
+    let mut suppressed = Array2::<f64>::zeros((height, width));
+    if height < 3 || width < 3 {
+        return suppressed;
+    }
+
+    for r in 1..height - 1 {
+        for c in 1..width - 1 {
+            let mut angle = gy[[r, c]].atan2(gx[[r, c]]).to_degrees();
+            if angle < 0.0 {
+                angle += 180.0;
+            }
+
+            let (n1, n2): ([usize; 2], [usize; 2]) = if !(22.5..157.5).contains(&angle) {
+                ([r, c - 1], [r, c + 1]) // ~0 degrees: horizontal gradient
+            } else if angle < 67.5 {
+                ([r - 1, c - 1], [r + 1, c + 1]) // ~45 degrees
+            } else if angle < 112.5 {
+                ([r - 1, c], [r + 1, c]) // ~90 degrees: vertical gradient
+            } else {
+                ([r - 1, c + 1], [r + 1, c - 1]) // ~135 degrees
+            };
+
+            let center = magnitude[[r, c]];
+            if center >= magnitude[n1] && center >= magnitude[n2] {
+                suppressed[[r, c]] = center;
+            }
+        }
+    }
+
+    suppressed
+}
+
+/// Double-threshold hysteresis: pixels at or above `high` are strong edges; pixels at or above
+/// `low` are kept only if connected, via an 8-neighborhood flood from the strong edges.
+/// Everything else is dropped.
+fn hysteresis_threshold(magnitude: &Array2<f64>, low: f64, high: f64) -> Array2<u8> {
+    let (height, width) = (magnitude.shape()[0], magnitude.shape()[1]);
+    let mut edges = Array2::<u8>::from_elem((height, width), 0);
+
+    let mut stack = Vec::new();
+    for r in 0..height {
+        for c in 0..width {
+            if magnitude[[r, c]] >= high {
+                edges[[r, c]] = u8::MAX;
+                stack.push((r, c));
+            }
+        }
+    }
+
+    while let Some((r, c)) = stack.pop() {
+        for dr in -1isize..=1 {
+            for dc in -1isize..=1 {
+                if dr == 0 && dc == 0 {
+                    continue;
+                }
+                let (rr, cc) = (r as isize + dr, c as isize + dc);
+                if rr < 0 || cc < 0 || rr >= height as isize || cc >= width as isize {
+                    continue;
+                }
+                let (rr, cc) = (rr as usize, cc as usize);
+                if edges[[rr, cc]] == 0 && magnitude[[rr, cc]] >= low {
+                    edges[[rr, cc]] = u8::MAX;
+                    stack.push((rr, cc));
+                }
+            }
+        }
+    }
+
+    edges
+}
+
 fn validate_image_path(img_path: &Path) -> PyResult<()> {
     let img_path_str = img_path.display().to_string();
     if !img_path_str.ends_with(".png") {
@@ -55,21 +289,324 @@ fn load_image(img_path: &Path) -> PyResult<Array2<u8>> {
         .map_err(|e| PyRuntimeError::new_err(e.to_string()))
 }
 
-/// Find indices of an intensity map where the value is maximum, i.e. the pixel is white.
-fn white_pixel_indices<T: Bounded + PartialEq>(vec: &[&T]) -> Vec<usize> {
-    let white = T::max_value();
+/// The homography mapping an axis-aligned `out_size` = `(height, width)` rectangle back onto the
+/// source `corners` quadrilateral (top-left, top-right, bottom-right, bottom-left).
+fn dest_to_source_homography(corners: &[(f64, f64); 4], out_size: (usize, usize)) -> Mat3 {
+    let (out_height, out_width) = out_size;
+    let dst_corners = [
+        (0.0, 0.0),
+        (out_width as f64 - 1.0, 0.0),
+        (out_width as f64 - 1.0, out_height as f64 - 1.0),
+        (0.0, out_height as f64 - 1.0),
+    ];
+    solve_homography(&dst_corners, corners)
+}
+
+/// Warps the `corners` quadrilateral of `image` into an axis-aligned rectangle of `out_size`,
+/// sampling the source with bilinear interpolation so intensity gradients survive the warp.
+fn warp_perspective(image: &Array2<u8>, corners: &[(f64, f64); 4], out_size: (usize, usize)) -> Array2<u8> {
+    let inverse = dest_to_source_homography(corners, out_size);
+    let (img_height, img_width) = (image.shape()[0], image.shape()[1]);
+    let (out_height, out_width) = out_size;
+
+    Array2::from_shape_fn((out_height, out_width), |(row, col)| {
+        let (src_col, src_row) = apply_homography(&inverse, col as f64, row as f64);
+        bilinear_sample(image, src_row, src_col, img_height, img_width)
+    })
+}
+
+/// Same as [`warp_perspective`], but samples `mask` with nearest-neighbor interpolation so a
+/// binary mask stays binary instead of picking up blended edge values.
+fn warp_mask(mask: &Array2<u8>, corners: &[(f64, f64); 4], out_size: (usize, usize)) -> Array2<u8> {
+    let inverse = dest_to_source_homography(corners, out_size);
+    let (mask_height, mask_width) = (mask.shape()[0], mask.shape()[1]);
+    let (out_height, out_width) = out_size;
+
+    Array2::from_shape_fn((out_height, out_width), |(row, col)| {
+        let (src_col, src_row) = apply_homography(&inverse, col as f64, row as f64);
+        nearest_sample(mask, src_row, src_col, mask_height, mask_width)
+    })
+}
+
+/// Samples `image` at fractional `(row, col)` by blending the 4 surrounding pixels, weighted by
+/// fractional distance. Out-of-bounds reads clamp to 0 rather than panicking.
+fn bilinear_sample(image: &Array2<u8>, row: f64, col: f64, height: usize, width: usize) -> u8 {
+    if row < 0.0 || col < 0.0 || row > (height - 1) as f64 || col > (width - 1) as f64 {
+        return 0;
+    }
+
+    let r0 = row.floor() as usize;
+    let c0 = col.floor() as usize;
+    let r1 = (r0 + 1).min(height - 1);
+    let c1 = (c0 + 1).min(width - 1);
+    let (fr, fc) = (row - r0 as f64, col - c0 as f64);
+
+    let top = image[[r0, c0]] as f64 * (1.0 - fc) + image[[r0, c1]] as f64 * fc;
+    let bottom = image[[r1, c0]] as f64 * (1.0 - fc) + image[[r1, c1]] as f64 * fc;
+    (top * (1.0 - fr) + bottom * fr).round() as u8
+}
+
+/// Samples `image` at fractional `(row, col)` by rounding to the nearest source pixel, clamping
+/// out-of-bounds reads to 0 like [`bilinear_sample`].
+fn nearest_sample(image: &Array2<u8>, row: f64, col: f64, height: usize, width: usize) -> u8 {
+    let row = row.round();
+    let col = col.round();
+    if row < 0.0 || col < 0.0 || row >= height as f64 || col >= width as f64 {
+        return 0;
+    }
+
+    image[[row as usize, col as usize]]
+}
+
+/// A pending Fast Marching Method update, ordered by tentative distance so a max-heap
+/// ([`BinaryHeap`]) pops the smallest distance first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FmmEntry {
+    distance: f64,
+    row: usize,
+    col: usize,
+}
+
+impl Eq for FmmEntry {}
+
+impl Ord for FmmEntry {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other
+            .distance
+            .partial_cmp(&self.distance)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    }
+}
+
+impl PartialOrd for FmmEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Computes the geodesic distance field from `grid`'s thresholded (>= `threshold`) wall pixels
+/// via the Fast Marching Method: wall pixels seed a min-heap at distance 0, then the smallest
+/// tentative distance is repeatedly frozen and propagated to its free 4-neighbors by
+/// [`eikonal_update`]. Thresholded `mask` pixels are excluded from the free region entirely.
+fn fast_marching_distance(grid: &Array2<u8>, mask: &Array2<u8>, threshold: u8) -> Array2<f64> {
+    let (height, width) = (grid.shape()[0], grid.shape()[1]);
+    let mut field = Array2::<f64>::from_elem((height, width), f64::INFINITY);
+    let mut frozen = Array2::<bool>::from_elem((height, width), false);
+    let mut heap = std::collections::BinaryHeap::new();
+
+    for ((r, c), &value) in grid.indexed_iter() {
+        if value >= threshold {
+            field[[r, c]] = 0.0;
+            frozen[[r, c]] = true;
+            heap.push(FmmEntry {
+                distance: 0.0,
+                row: r,
+                col: c,
+            });
+        }
+    }
+
+    while let Some(FmmEntry { distance, row, col }) = heap.pop() {
+        if distance > field[[row, col]] {
+            continue; // stale entry, already superseded by a smaller update
+        }
+        frozen[[row, col]] = true;
+
+        for (dr, dc) in [(-1isize, 0isize), (1, 0), (0, -1), (0, 1)] {
+            let (nr, nc) = (row as isize + dr, col as isize + dc);
+            if nr < 0 || nc < 0 || nr as usize >= height || nc as usize >= width {
+                continue;
+            }
+            let (nr, nc) = (nr as usize, nc as usize);
+            if frozen[[nr, nc]] || mask[[nr, nc]] >= threshold {
+                continue;
+            }
+
+            let updated = eikonal_update(&field, &frozen, height, width, nr, nc);
+            if updated < field[[nr, nc]] {
+                field[[nr, nc]] = updated;
+                heap.push(FmmEntry {
+                    distance: updated,
+                    row: nr,
+                    col: nc,
+                });
+            }
+        }
+    }
+
+    field
+}
+
+/// Solves the Eikonal update at `(row, col)` from its already-frozen neighbors: the quadratic
+/// `(d - dx)^2 + (d - dy)^2 = 1`, where `dx`/`dy` are the smaller frozen neighbor distance along
+/// each axis. Falls back to the one-sided update `d = min_neighbor + 1` when only one axis has a
+/// frozen neighbor, or when the two-axis solution would be numerically invalid (the axes
+/// disagree by more than the unit step).
+fn eikonal_update(
+    field: &Array2<f64>,
+    frozen: &Array2<bool>,
+    height: usize,
+    width: usize,
+    row: usize,
+    col: usize,
+) -> f64 {
+    let frozen_neighbor = |r: isize, c: isize| -> Option<f64> {
+        if r < 0 || c < 0 || r as usize >= height || c as usize >= width {
+            return None;
+        }
+        let (r, c) = (r as usize, c as usize);
+        frozen[[r, c]].then_some(field[[r, c]])
+    };
+
+    let row = row as isize;
+    let col = col as isize;
+    let dy = [frozen_neighbor(row - 1, col), frozen_neighbor(row + 1, col)]
+        .into_iter()
+        .flatten()
+        .reduce(f64::min);
+    let dx = [frozen_neighbor(row, col - 1), frozen_neighbor(row, col + 1)]
+        .into_iter()
+        .flatten()
+        .reduce(f64::min);
+
+    match (dx, dy) {
+        (Some(dx), Some(dy)) => {
+            let diff = (dx - dy).abs();
+            if diff >= 1.0 {
+                dx.min(dy) + 1.0
+            } else {
+                (dx + dy + (2.0 - diff * diff).sqrt()) / 2.0
+            }
+        }
+        (Some(d), None) | (None, Some(d)) => d + 1.0,
+        (None, None) => 1.0,
+    }
+}
+
+/// Estimates cell widths from the ridge of `grid`'s Fast Marching distance field, which is
+/// robust to curved or irregular cell boundaries. A ridge pixel sits midway between two walls,
+/// so the reported delta is twice its distance there.
+fn fast_marching_deltas(grid: &Array2<u8>, mask: &Array2<u8>, threshold: u8) -> Vec<usize> {
+    let field = fast_marching_distance(grid, mask, threshold);
+    let (height, width) = (field.shape()[0], field.shape()[1]);
+    let mut deltas = Vec::new();
+    for r in 0..height {
+        for c in 1..width.saturating_sub(1) {
+            let center = field[[r, c]];
+            if center.is_finite() && center > 0.0 && center > field[[r, c - 1]] && center > field[[r, c + 1]] {
+                deltas.push((2.0 * center).round() as usize);
+            }
+        }
+    }
+
+    deltas
+}
+
+/// Builds the 4 corners (in `(x, y)` pixel coordinates) of the rectangle stroking the segment
+/// from `p0` to `p1` at `width` px wide. A zero-length segment becomes a small square centered
+/// on the point. Endpoints are canonicalized to a consistent order first, so a polyline that
+/// doubles back on itself strokes the same quad both times.
+fn stroke_quad(p0: (f64, f64), p1: (f64, f64), width: f64) -> [(f64, f64); 4] {
+    let (p0, p1) = if p1 < p0 { (p1, p0) } else { (p0, p1) };
+    let half = width / 2.0;
+    let (dx, dy) = (p1.0 - p0.0, p1.1 - p0.1);
+    let len = (dx * dx + dy * dy).sqrt();
+    if len < 1e-9 {
+        return [
+            (p0.0 - half, p0.1 - half),
+            (p0.0 + half, p0.1 - half),
+            (p0.0 + half, p0.1 + half),
+            (p0.0 - half, p0.1 + half),
+        ];
+    }
+
+    let (nx, ny) = (-dy / len * half, dx / len * half);
+    [
+        (p0.0 + nx, p0.1 + ny),
+        (p1.0 + nx, p1.1 + ny),
+        (p1.0 - nx, p1.1 - ny),
+        (p0.0 - nx, p0.1 - ny),
+    ]
+}
+
+/// Accumulates the signed scanline coverage delta of closed polygon `corners` into `deltas`: for
+/// each non-horizontal edge, a signed delta (+1 descending, -1 ascending) is added at the column
+/// where the edge crosses each row it spans. Summing a row's deltas left-to-right gives the
+/// nonzero winding number at every pixel in that row.
+fn accumulate_polygon_coverage(corners: &[(f64, f64)], deltas: &mut Array2<i32>) {
+    let (height, width) = (deltas.shape()[0], deltas.shape()[1]);
+    if height == 0 || width == 0 {
+        return;
+    }
+    let edges = corners.iter().zip(corners.iter().cycle().skip(1));
+    for (&(x0, y0), &(x1, y1)) in edges {
+        if (y0 - y1).abs() < 1e-9 {
+            continue; // horizontal edges never cross a scanline
+        }
+
+        let (top, bottom, sign) = if y0 < y1 { (y0, y1, 1) } else { (y1, y0, -1) };
+        let row_start = top.ceil().max(0.0) as usize;
+        let row_end = (bottom.ceil() as isize).clamp(0, height as isize) as usize;
+        for row in row_start..row_end {
+            let t = (row as f64 - y0) / (y1 - y0);
+            let x = x0 + t * (x1 - x0);
+            let col = (x.round() as isize).clamp(0, width as isize - 1) as usize;
+            deltas[[row, col]] += sign;
+        }
+    }
+}
+
+/// Rasterizes `polylines` (each a sequence of `(x, y)` points) into a binary edge map of
+/// `out_size` = `(height, width)`, stroking every segment to `stroke_width` px wide. Each stroked
+/// segment becomes a rectangle whose edges contribute a coverage delta per row ([`stroke_quad`],
+/// [`accumulate_polygon_coverage`]); sweeping each row's deltas left-to-right gives the winding
+/// number at every pixel, nonzero meaning "inside a stroke".
+pub fn rasterize_polylines(
+    polylines: &[Vec<(f64, f64)>],
+    out_size: (usize, usize),
+    stroke_width: f64,
+) -> Array2<u8> {
+    let mut deltas = Array2::<i32>::zeros(out_size);
+    for polyline in polylines {
+        for segment in polyline.windows(2) {
+            let quad = stroke_quad(segment[0], segment[1], stroke_width);
+            accumulate_polygon_coverage(&quad, &mut deltas);
+        }
+    }
+
+    let (height, width) = out_size;
+    let mut image = Array2::<u8>::zeros(out_size);
+    for row in 0..height {
+        let mut winding = 0;
+        for col in 0..width {
+            winding += deltas[[row, col]];
+            if winding != 0 {
+                image[[row, col]] = u8::MAX;
+            }
+        }
+    }
+
+    image
+}
+
+/// Finds indices of an intensity map where the value is at or above `threshold`, i.e. the pixel
+/// counts as an edge (or mask) pixel.
+fn thresholded_pixel_indices<T: PartialOrd>(vec: &[&T], threshold: &T) -> Vec<usize> {
     vec.iter()
         .enumerate()
-        .filter(|(_, &val)| *val == white)
+        .filter(|(_, &val)| val >= threshold)
         .map(|(idx, _)| idx)
         .collect::<Vec<_>>()
 }
 
-/// Gets all distances between cell edges within a single image. A mask is required, but may be
-/// all false (i.e. no masking).
-fn all_pixel_deltas<T: Bounded + PartialEq>(
-    image: Array2<T>,
-    mask: Array2<T>,
+/// Gets all distances between cell edges within a single image, scanning along `axis` (`Axis(0)`
+/// for rows/horizontal, `Axis(1)` for columns/vertical). A mask is required, but may be all
+/// false (i.e. no masking).
+fn all_pixel_deltas<T: PartialOrd + Copy>(
+    image: &Array2<T>,
+    mask: &Array2<T>,
+    threshold: T,
+    axis: Axis,
 ) -> PyResult<Vec<usize>> {
     if image.shape() != mask.shape() {
         let msg = format!(
@@ -80,33 +617,102 @@ fn all_pixel_deltas<T: Bounded + PartialEq>(
         return Err(PyValueError::new_err(msg));
     }
 
-    let axis = Axis(0);
     let mut diffs = Vec::new();
-    let img_height = image.shape()[0] as isize;
-    for row in 0..img_height {
-        // take the whole row
-        let indices = Slice::new(row, Some(row + 1), 1);
-        let row_img = image
+    let len = image.shape()[axis.index()] as isize;
+    for i in 0..len {
+        // take the whole row/column
+        let indices = Slice::new(i, Some(i + 1), 1);
+        let line_img = image
             .slice_axis(axis, indices)
             .into_iter()
             .collect::<Vec<&T>>();
-        let row_mask = mask
+        let line_mask = mask
             .slice_axis(axis, indices)
             .into_iter()
             .collect::<Vec<&T>>();
-        let mut row_diffs = pixel_deltas_from_row(row_img, row_mask);
-        diffs.append(&mut row_diffs);
+        let mut line_diffs = pixel_deltas_from_row(line_img, line_mask, threshold);
+        diffs.append(&mut line_diffs);
     }
 
     Ok(diffs)
 }
 
-/// Get all pixel distances between cell boundaries for a single row in an image
-fn pixel_deltas_from_row<T: Bounded + PartialEq>(row: Vec<&T>, row_mask: Vec<&T>) -> Vec<usize> {
-    let white = T::max_value();
+/// Gets all distances between cell edges along scan-lines at `angle_deg` degrees from
+/// horizontal. 0 and 90 degrees (mod 180) reuse the existing row/column slicing directly; other
+/// angles walk parallel scan-lines 1 px apart, sampling `image`/`mask` with nearest-neighbor
+/// interpolation at unit steps.
+fn pixel_deltas_along_angle<T: PartialOrd + Copy>(
+    image: &Array2<T>,
+    mask: &Array2<T>,
+    threshold: T,
+    angle_deg: f64,
+) -> PyResult<Vec<usize>> {
+    if image.shape() != mask.shape() {
+        let msg = format!(
+            "Shape mismatch: img={:?}, mask={:?}",
+            image.shape(),
+            mask.shape()
+        );
+        return Err(PyValueError::new_err(msg));
+    }
+
+    let normalized = angle_deg.rem_euclid(180.0);
+    if normalized.abs() < 1e-9 {
+        return all_pixel_deltas(image, mask, threshold, Axis(0));
+    }
+    if (normalized - 90.0).abs() < 1e-9 {
+        return all_pixel_deltas(image, mask, threshold, Axis(1));
+    }
+
+    let (height, width) = (image.shape()[0] as f64, image.shape()[1] as f64);
+    let theta = normalized.to_radians();
+    let (along_row, along_col) = (theta.sin(), theta.cos());
+    let (perp_row, perp_col) = (along_col, -along_row);
+
+    let half_diag = ((height * height + width * width).sqrt() / 2.0).ceil() as isize + 1;
+    let (center_row, center_col) = (height / 2.0, width / 2.0);
+
+    let mut diffs = Vec::new();
+    for offset in -half_diag..=half_diag {
+        let offset = offset as f64;
+        let base_row = center_row + offset * perp_row;
+        let base_col = center_col + offset * perp_col;
 
+        let mut line_img: Vec<T> = Vec::new();
+        let mut line_mask: Vec<T> = Vec::new();
+        for step in -half_diag..=half_diag {
+            let step = step as f64;
+            let row = (base_row + step * along_row).round();
+            let col = (base_col + step * along_col).round();
+            if row < 0.0 || col < 0.0 || row >= height || col >= width {
+                continue;
+            }
+            let (row, col) = (row as usize, col as usize);
+            line_img.push(image[[row, col]]);
+            line_mask.push(mask[[row, col]]);
+        }
+
+        if line_img.is_empty() {
+            continue;
+        }
+
+        let row_img = line_img.iter().collect::<Vec<&T>>();
+        let row_mask = line_mask.iter().collect::<Vec<&T>>();
+        let mut line_diffs = pixel_deltas_from_row(row_img, row_mask, threshold);
+        diffs.append(&mut line_diffs);
+    }
+
+    Ok(diffs)
+}
+
+/// Get all pixel distances between cell boundaries for a single row in an image
+fn pixel_deltas_from_row<T: PartialOrd + Copy>(
+    row: Vec<&T>,
+    row_mask: Vec<&T>,
+    threshold: T,
+) -> Vec<usize> {
     // find indices to split row into sub-rows
-    let mut mask_split_indices = white_pixel_indices(row_mask.as_slice());
+    let mut mask_split_indices = thresholded_pixel_indices(row_mask.as_slice(), &threshold);
 
     // make sure we go to the end of the image
     mask_split_indices.push(row.len());
@@ -116,9 +722,9 @@ fn pixel_deltas_from_row<T: Bounded + PartialEq>(row: Vec<&T>, row_mask: Vec<&T>
     let mut row_diffs: Vec<usize> = Vec::new();
     for idx_end in mask_split_indices {
         // avoid negative usize overflow panic and skip adjacent pixels
-        if (idx_end == 0) || !(*row_mask[idx_end - 1] == white) {
+        if (idx_end == 0) || (*row_mask[idx_end - 1] < threshold) {
             let split = &row.as_slice()[idx_start..idx_end];
-            let mut sub_diffs = pixel_deltas_from_masked_run(split);
+            let mut sub_diffs = pixel_deltas_from_masked_run(split, threshold);
             row_diffs.append(&mut sub_diffs);
         }
         // increment regardless of whether the current pixel was usable so we don't
@@ -133,8 +739,8 @@ fn pixel_deltas_from_row<T: Bounded + PartialEq>(row: Vec<&T>, row_mask: Vec<&T>
 /// throws out the others (i.e. it only accepts measurements where the boundary location is >1 px
 /// away from the previous boundary location). The distance between the leftmost and rightmost
 /// adjacent locations is not counted.
-fn pixel_deltas_from_masked_run<T: Bounded + PartialEq>(sub_row: &[&T]) -> Vec<usize> {
-    let edges = white_pixel_indices(sub_row);
+fn pixel_deltas_from_masked_run<T: PartialOrd + Copy>(sub_row: &[&T], threshold: T) -> Vec<usize> {
+    let edges = thresholded_pixel_indices(sub_row, &threshold);
     let mut diffs = Vec::new();
     let mut last_edge_idx = 0;
     for (idx_no, idx) in edges.iter().enumerate() {
@@ -298,7 +904,7 @@ mod tests {
             &u8::MAX,
         ];
         let good: [usize; 3] = [3, 2, 2];
-        let result = pixel_deltas_from_masked_run(sub_row);
+        let result = pixel_deltas_from_masked_run(sub_row, u8::MAX);
         assert_eq!(result, good);
 
         Ok(())
@@ -335,8 +941,194 @@ mod tests {
             &u8::MAX,
         ];
         let good: [usize; 2] = [3, 2];
-        let result = pixel_deltas_from_row(row, row_mask);
+        let result = pixel_deltas_from_row(row, row_mask, u8::MAX);
+        assert_eq!(result, good);
+    }
+
+    #[test]
+    fn test_get_diffs_from_row_with_lower_threshold() {
+        // same mask column as `test_get_diffs_from_row`, but the edges are mid-gray rather than
+        // fully white, so they're only picked up once the threshold is lowered to match
+        let row = vec![
+            &128u8, &0, &0, &128, &128, &128, &0, &128, &0, &128, &128, &0,
+        ];
+        let row_mask = vec![&0u8, &0, &0, &0, &128, &0, &0, &0, &128, &0, &0, &128];
+        let good: [usize; 2] = [3, 2];
+        let result = pixel_deltas_from_row(row, row_mask, 128);
         assert_eq!(result, good);
+
+        // at the default threshold the mid-gray pixels don't count as edges at all
+        let row = vec![
+            &128u8, &0, &0, &128, &128, &128, &0, &128, &0, &128, &128, &0,
+        ];
+        let row_mask = vec![&0u8, &0, &0, &0, &128, &0, &0, &0, &128, &0, &0, &128];
+        let result = pixel_deltas_from_row(row, row_mask, u8::MAX);
+        assert!(result.is_empty());
+    }
+
+    mod test_warp_perspective {
+        use super::super::*;
+
+        #[test]
+        fn identity_quad_is_unchanged() {
+            let image = Array2::<u8>::from_shape_vec((4, 4), (0u8..16).collect()).unwrap();
+            let corners = [(0.0, 0.0), (3.0, 0.0), (3.0, 3.0), (0.0, 3.0)];
+            let warped = warp_perspective(&image, &corners, (4, 4));
+            assert_eq!(warped, image);
+        }
+
+        #[test]
+        fn out_of_bounds_reads_clamp_to_zero() {
+            let image = Array2::<u8>::from_elem((2, 2), u8::MAX);
+            // corners well outside the 2x2 source, so every sample falls off the edge
+            let corners = [(10.0, 10.0), (13.0, 10.0), (13.0, 13.0), (10.0, 13.0)];
+            let warped = warp_perspective(&image, &corners, (2, 2));
+            assert!(warped.iter().all(|&px| px == 0));
+        }
+
+        #[test]
+        fn bilinear_interpolates_between_pixels() {
+            let image =
+                Array2::<u8>::from_shape_vec((2, 2), vec![0, 100, 0, 100]).unwrap();
+            // stretching the 2-column source to 4 output columns puts output column 1 a third of
+            // the way between the 0 and 100 columns, so its interpolated value is neither
+            let corners = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+            let warped = warp_perspective(&image, &corners, (2, 4));
+            assert!(warped[[0, 1]] > 0 && warped[[0, 1]] < 100);
+        }
+    }
+
+    mod test_warp_mask {
+        use super::super::*;
+
+        #[test]
+        fn identity_quad_is_unchanged() {
+            let mask = Array2::<u8>::from_shape_vec((4, 4), (0u8..16).collect()).unwrap();
+            let corners = [(0.0, 0.0), (3.0, 0.0), (3.0, 3.0), (0.0, 3.0)];
+            let warped = warp_mask(&mask, &corners, (4, 4));
+            assert_eq!(warped, mask);
+        }
+
+        #[test]
+        fn stays_binary_instead_of_blending() {
+            let mask = Array2::<u8>::from_shape_vec((2, 2), vec![0, u8::MAX, 0, u8::MAX]).unwrap();
+            let corners = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+            let warped = warp_mask(&mask, &corners, (2, 4));
+            assert!(warped
+                .iter()
+                .all(|&px| px == 0 || px == u8::MAX));
+        }
+    }
+
+    mod test_fast_marching_deltas {
+        use super::super::*;
+
+        #[test]
+        fn straight_corridor_ridge_is_midway_between_walls() {
+            // a 1x7 corridor walled at both ends; the geodesic ridge sits at the midpoint,
+            // 3 px from each wall, so the reported cell width is 6
+            let image =
+                Array2::<u8>::from_shape_vec((1, 7), vec![u8::MAX, 0, 0, 0, 0, 0, u8::MAX])
+                    .unwrap();
+            let mask = Array2::<u8>::zeros((1, 7));
+            let deltas = fast_marching_deltas(&image, &mask, u8::MAX);
+            assert_eq!(deltas, vec![6]);
+        }
+
+        #[test]
+        fn masked_pixels_are_excluded_from_the_free_region() {
+            let image = Array2::<u8>::from_shape_vec(
+                (1, 5),
+                vec![u8::MAX, 0, 0, 0, u8::MAX],
+            )
+            .unwrap();
+            let mut mask = Array2::<u8>::zeros((1, 5));
+            mask[[0, 2]] = u8::MAX;
+            let field = fast_marching_distance(&image, &mask, u8::MAX);
+            assert_eq!(field[[0, 2]], f64::INFINITY);
+        }
+    }
+
+    mod test_canny_edges {
+        use super::super::*;
+
+        #[test]
+        fn gaussian_kernel_is_normalized_and_symmetric() {
+            let kernel = gaussian_kernel_1d(1.0);
+            let sum: f64 = kernel.iter().sum();
+            assert!((sum - 1.0).abs() < 1e-9);
+            for i in 0..kernel.len() / 2 {
+                assert!((kernel[i] - kernel[kernel.len() - 1 - i]).abs() < 1e-12);
+            }
+        }
+
+        #[test]
+        fn finds_a_vertical_step_edge() {
+            // a sharp light/dark boundary down the middle column should produce a single
+            // column of edge pixels at (roughly) that boundary, and nothing elsewhere
+            let mut image = Array2::<u8>::from_elem((10, 10), 0);
+            for r in 0..10 {
+                for c in 5..10 {
+                    image[[r, c]] = u8::MAX;
+                }
+            }
+            let edges = canny_edges(&image, 1.0, 50.0, 100.0);
+            let edge_cols = find_true_pixel_columns(&edges);
+            assert!(!edge_cols.is_empty());
+            for col in edge_cols {
+                assert!((3..=7).contains(&col), "unexpected edge column: {col}");
+            }
+        }
+
+        #[test]
+        fn blank_image_has_no_edges() {
+            let image = Array2::<u8>::from_elem((8, 8), 128);
+            let edges = canny_edges(&image, 1.0, 50.0, 100.0);
+            assert!(edges.iter().all(|&px| px == 0));
+        }
+
+        fn find_true_pixel_columns(edges: &Array2<u8>) -> Vec<usize> {
+            edges
+                .indexed_iter()
+                .filter(|&(_, &px)| px == u8::MAX)
+                .map(|((_, c), _)| c)
+                .collect()
+        }
+    }
+
+    mod test_rasterize_polylines {
+        use super::super::*;
+
+        #[test]
+        fn horizontal_stroke_marks_a_band_of_rows() {
+            let polylines = vec![vec![(1.0, 3.0), (8.0, 3.0)]];
+            let image = rasterize_polylines(&polylines, (7, 10), 3.0);
+            for row in 2..=4 {
+                assert_eq!(image[[row, 4]], u8::MAX);
+            }
+            assert_eq!(image[[0, 4]], 0);
+            assert_eq!(image[[6, 4]], 0);
+        }
+
+        #[test]
+        fn point_with_no_neighbors_produces_no_stroke() {
+            let polylines = vec![vec![(3.0, 3.0)]];
+            let image = rasterize_polylines(&polylines, (7, 7), 2.0);
+            assert!(image.iter().all(|&px| px == 0));
+        }
+
+        #[test]
+        fn empty_polylines_produce_a_blank_image() {
+            let image = rasterize_polylines(&[], (4, 4), 1.0);
+            assert!(image.iter().all(|&px| px == 0));
+        }
+
+        #[test]
+        fn a_polyline_that_doubles_back_does_not_erase_its_own_stroke() {
+            let polylines = vec![vec![(1.0, 3.0), (8.0, 3.0), (1.0, 3.0)]];
+            let image = rasterize_polylines(&polylines, (7, 10), 3.0);
+            assert_eq!(image[[3, 4]], u8::MAX);
+        }
     }
 
     mod test_get_all_diffs {
@@ -415,7 +1207,7 @@ mod tests {
             .concat();
             let mask = Array2::<u8>::from_shape_vec((img_height, img_width), mask).unwrap();
             let good = [2, 3, 2, 3, 2];
-            let result = all_pixel_deltas(image, mask).unwrap();
+            let result = all_pixel_deltas(&image, &mask, u8::MAX, Axis(0)).unwrap();
             assert_eq!(result, good);
         }
 
@@ -493,7 +1285,7 @@ mod tests {
             .concat();
             let mask = Array2::<u8>::from_shape_vec((img_height, img_width), mask).unwrap();
             let good = [2, 3, 2, 2, 3, 2, 3];
-            let result = all_pixel_deltas(image, mask).unwrap();
+            let result = all_pixel_deltas(&image, &mask, u8::MAX, Axis(0)).unwrap();
             assert_eq!(result, good);
         }
 
@@ -501,7 +1293,7 @@ mod tests {
         fn test_shape_mismatch() {
             let image = Array2::<u8>::zeros((1, 4));
             let mask = Array2::<u8>::zeros((4, 59));
-            let result = all_pixel_deltas(image, mask);
+            let result = all_pixel_deltas(&image, &mask, u8::MAX, Axis(0));
             assert!(result.is_err());
             assert!(result
                 .err()
@@ -510,4 +1302,66 @@ mod tests {
                 .contains("Shape mismatch: img="));
         }
     }
+
+    mod test_pixel_deltas_along_angle {
+        use super::super::*;
+
+        #[test]
+        fn zero_degrees_matches_horizontal_scan() {
+            let image = Array2::<u8>::from_shape_vec(
+                (2, 4),
+                vec![
+                    u8::MAX,
+                    u8::MIN,
+                    u8::MIN,
+                    u8::MAX,
+                    u8::MAX,
+                    u8::MIN,
+                    u8::MIN,
+                    u8::MAX,
+                ],
+            )
+            .unwrap();
+            let mask = Array2::<u8>::zeros((2, 4));
+            let result = pixel_deltas_along_angle(&image, &mask, u8::MAX, 0.0).unwrap();
+            assert_eq!(result, all_pixel_deltas(&image, &mask, u8::MAX, Axis(0)).unwrap());
+        }
+
+        #[test]
+        fn ninety_degrees_matches_vertical_scan() {
+            let image = Array2::<u8>::from_shape_vec(
+                (4, 2),
+                vec![
+                    u8::MAX,
+                    u8::MAX,
+                    u8::MIN,
+                    u8::MIN,
+                    u8::MIN,
+                    u8::MIN,
+                    u8::MAX,
+                    u8::MAX,
+                ],
+            )
+            .unwrap();
+            let mask = Array2::<u8>::zeros((4, 2));
+            let result = pixel_deltas_along_angle(&image, &mask, u8::MAX, 90.0).unwrap();
+            assert_eq!(result, all_pixel_deltas(&image, &mask, u8::MAX, Axis(1)).unwrap());
+        }
+
+        #[test]
+        fn forty_five_degrees_finds_no_edges_in_a_blank_image() {
+            let image = Array2::<u8>::from_elem((5, 5), u8::MIN);
+            let mask = Array2::<u8>::zeros((5, 5));
+            let result = pixel_deltas_along_angle(&image, &mask, u8::MAX, 45.0).unwrap();
+            assert!(result.is_empty());
+        }
+
+        #[test]
+        fn shape_mismatch_is_an_error_at_any_angle() {
+            let image = Array2::<u8>::zeros((1, 4));
+            let mask = Array2::<u8>::zeros((4, 59));
+            let result = pixel_deltas_along_angle(&image, &mask, u8::MAX, 45.0);
+            assert!(result.is_err());
+        }
+    }
 }